@@ -3,7 +3,7 @@ use serde::Deserialize;
 #[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 pub struct UserInfo {
-    name: String,
+    pub(crate) name: String,
     email: String,
     date: String,
 }
@@ -17,9 +17,9 @@ pub struct Tree {
 #[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 pub struct Commit {
-    author: UserInfo,
+    pub(crate) author: UserInfo,
     committer: UserInfo,
-    message: String,
+    pub(crate) message: String,
     tree: Tree,
 }
 
@@ -40,9 +40,29 @@ pub struct Ref {
 #[derive(Debug, Deserialize)]
 pub struct CommitResponse {
     pub sha: String,
-    commit: Commit,
+    pub(crate) commit: Commit,
     url: String,
     author: User,
     committer: User,
     parents: Vec<Ref>,
 }
+
+/// A commit as returned by the GitLab `repository/commits` endpoint
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+pub struct GitlabCommitResponse {
+    pub id: String,
+    short_id: String,
+    pub(crate) title: String,
+    pub(crate) author_name: String,
+}
+
+/// A commit as returned by the Gitea/Forgejo `repos/{owner}/{repo}/commits`
+/// endpoint
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+pub struct ForgejoCommitResponse {
+    pub sha: String,
+    pub(crate) commit: Commit,
+    url: String,
+}
@@ -7,8 +7,14 @@ pub type Result<T> = core::result::Result<T, Error>;
 // them into log messages and have a generic internal error to show to the user
 #[derive(Error, Debug)]
 pub(crate) enum BrunError {
-    #[error("you must set the GH_TOKEN or GITHUB_TOKEN environment variable")]
-    MissingToken,
+    #[error("you must pass --webhook-secret or set GH_WEBHOOK_SECRET when using --webhook")]
+    MissingWebhookSecret,
+    #[error("webhook request body too large ({0} bytes, limit is {1})")]
+    WebhookBodyTooLarge(usize, usize),
+    #[error("--debounce is not supported together with --webhook: pushes are discrete events, so there is no burst to coalesce")]
+    WebhookDebounceUnsupported,
+    #[error("no token found for the selected forge ({0}); set the appropriate token environment variable")]
+    MissingForgeToken(String),
     #[error("there are uncommitted changes. Run `git commit`or `git stash` to save the changes and try again.")]
     GitDirty,
     #[error("remote repository has no commits")]
@@ -23,6 +29,10 @@ pub(crate) enum BrunError {
     GitNoUpstreamURL(i32, String),
     #[error("could not get remote name from upstream branch: {0}")]
     GitBadRemote(String),
+    #[error("failed to list remote branch (code={0}): {1}")]
+    GitNoRemoteBranch(i32, String),
+    #[error("failed to update submodules (code={0}): {1}")]
+    GitSubmoduleUpdate(i32, String),
 
     // Execution Failure
     #[error("user command failed (code={0}): {1}")]
@@ -23,8 +23,18 @@ pub(crate) fn git_head() -> Result<String> {
     Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
-/// Get the remote username and repo_name from the git remote information
-pub(crate) fn git_upstream_info(branch: &str) -> Result<(String, String)> {
+/// The remote repository information parsed out of the branch's upstream
+#[derive(Debug)]
+pub(crate) struct UpstreamInfo {
+    pub username: String,
+    pub repo_name: String,
+    pub remote: String,
+    pub host: String,
+}
+
+/// Get the remote username, repo_name, remote name and host from the git
+/// remote information
+pub(crate) fn git_upstream_info(branch: &str) -> Result<UpstreamInfo> {
     let output = Command::new("git")
         .arg("rev-parse")
         .arg("--abbrev-ref")
@@ -47,7 +57,8 @@ pub(crate) fn git_upstream_info(branch: &str) -> Result<(String, String)> {
     let remote = upstream
         .rsplit('/')
         .nth(1)
-        .ok_or(Error::GitBadRemote(upstream.clone()))?;
+        .ok_or(Error::GitBadRemote(upstream.clone()))?
+        .to_string();
 
     trace!("found upstream remote={}", &remote);
 
@@ -55,7 +66,7 @@ pub(crate) fn git_upstream_info(branch: &str) -> Result<(String, String)> {
     let output = Command::new("git")
         .arg("remote")
         .arg("get-url")
-        .arg(remote)
+        .arg(&remote)
         .output()
         .map_err(|_| Error::CommandFailure("git remote get-url <remote>".to_string()))?;
 
@@ -71,29 +82,29 @@ pub(crate) fn git_upstream_info(branch: &str) -> Result<(String, String)> {
     trace!("found upstream remote url={}", &url);
 
     // Parse the URL
-    let (username, repo_name) = if url.starts_with("git@") {
-        // It's an SSH URL
-        let repo_uri = url
-            .rsplit(':')
-            .next()
+    let (host, username, repo_name) = if let Some(rest) = url.strip_prefix("git@") {
+        // It's an SSH URL: git@host:user/repo(.git)
+        let (host, repo_uri) = rest
+            .split_once(':')
             .ok_or(Error::GitBadRemote(url.clone()))?;
-        repo_uri
+        let (username, repo_name) = repo_uri
             .split_once('/')
-            .expect("the repo uri to have a slash")
+            .ok_or(Error::GitBadRemote(url.clone()))?;
+
+        (host, username, repo_name)
     } else {
-        // It's an HTTP(s) URL
-        assert!(url.starts_with("http"));
-
-        let mut uri_parts = url.split('/');
-        let repo_name = uri_parts
-            .next()
-            .expect("split to return at least one result");
-        let username = uri_parts.next().ok_or(Error::InternalError(format!(
-            "the URI to have at least one slash: {}",
-            url
-        )))?;
-
-        (username, repo_name)
+        // It's an HTTP(s) URL: scheme://host/user/repo(.git)
+        let without_scheme = url
+            .split_once("://")
+            .map(|(_, rest)| rest)
+            .ok_or(Error::GitBadRemote(url.clone()))?;
+
+        let mut uri_parts = without_scheme.splitn(3, '/');
+        let host = uri_parts.next().ok_or(Error::GitBadRemote(url.clone()))?;
+        let username = uri_parts.next().ok_or(Error::GitBadRemote(url.clone()))?;
+        let repo_name = uri_parts.next().ok_or(Error::GitBadRemote(url.clone()))?;
+
+        (host, username, repo_name)
     };
 
     // Trim the `.git` suffix, it it's there
@@ -104,7 +115,61 @@ pub(crate) fn git_upstream_info(branch: &str) -> Result<(String, String)> {
         repo_name
     };
 
-    Ok((username.to_string(), repo_name.to_string()))
+    Ok(UpstreamInfo {
+        username: username.to_string(),
+        repo_name: repo_name.to_string(),
+        remote,
+        host: host.to_string(),
+    })
+}
+
+/// Get the SHA of `branch`'s tip on `remote` straight from the remote, with
+/// no local fetch required
+pub(crate) fn git_ls_remote(remote: &str, branch: &str) -> Result<String> {
+    let output = Command::new("git")
+        .arg("ls-remote")
+        .arg("--exit-code")
+        .arg(remote)
+        .arg(format!("refs/heads/{}", branch))
+        .output()
+        .map_err(|_| Error::CommandFailure("git ls-remote".to_string()))?;
+
+    if !output.status.success() {
+        return Err(Error::GitNoRemoteBranch(
+            output.status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let sha = stdout
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().next())
+        .ok_or(Error::GitEmptyHistory)?;
+
+    Ok(sha.to_string())
+}
+
+/// Recursively initialize and update all submodules to the revision recorded
+/// in the superproject
+pub(crate) fn git_submodule_update_recursive() -> Result<()> {
+    let output = Command::new("git")
+        .arg("submodule")
+        .arg("update")
+        .arg("--init")
+        .arg("--recursive")
+        .output()
+        .map_err(|_| Error::CommandFailure("git submodule update".to_string()))?;
+
+    if !output.status.success() {
+        return Err(Error::GitSubmoduleUpdate(
+            output.status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+
+    Ok(())
 }
 
 /// Check if a repository has unstashed changes, which would avoid pulling
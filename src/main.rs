@@ -1,14 +1,25 @@
 mod commit;
 mod error;
 mod git;
+mod notify;
+mod remote;
+mod webhook;
 
-use log::{debug, trace};
-use std::io::{self, Write};
-use std::{process::Command, time::Duration};
+use log::{debug, trace, warn};
+use std::io::{self, Read, Write};
+use std::{
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
 
 use clap::Parser;
+use lettre::transport::smtp::authentication::Credentials;
 
 use error::{Error, Result};
+use notify::{email::EmailNotifier, webhook::WebhookNotifier, Notifier, RunEvent};
+use remote::{
+    forgejo::Forgejo, github::Github, gitlab::Gitlab, local::Local, CommitInfo, Forge, Remote,
+};
 
 /// Listen for changes on the upstream for the currently checked out branch,
 /// and when a change is found, pull them and run the given command
@@ -23,64 +34,60 @@ struct Args {
     #[arg(long)]
     stop_on_failure: bool,
 
-    /// The command to run on upstream changes. NOTE: this is run in a subshell
-    #[arg(last = true, required = true)]
-    cmd: Vec<String>,
-}
+    /// After a successful pull, also run `git submodule update --init --recursive`
+    #[arg(long)]
+    recurse_submodules: bool,
 
-#[derive(Debug)]
-struct GithubState {
-    username: String,
-    repo: String,
-    branch: String,
-    token: String,
-    client: reqwest::blocking::Client,
-    last_commit: Option<String>,
-}
+    /// Run `cmd` once immediately after startup, against the current tip
+    #[arg(long)]
+    run_on_start: bool,
+
+    /// After detecting a change, wait for the remote to stop moving for this
+    /// many seconds before pulling and running, collapsing a burst of
+    /// upstream commits into a single run. Only applies when polling; an
+    /// error to combine with --webhook, which has no burst to coalesce
+    #[arg(default_value = "0", long)]
+    debounce: f64,
+
+    /// Instead of polling, start an HTTP server on `addr:port` and react to
+    /// GitHub `push` webhook deliveries
+    #[arg(long, value_name = "addr:port")]
+    webhook: Option<String>,
+
+    /// The shared secret used to verify the `X-Hub-Signature-256` header on
+    /// webhook requests. Falls back to GH_WEBHOOK_SECRET
+    #[arg(long)]
+    webhook_secret: Option<String>,
 
-impl GithubState {
-    fn new(username: String, repo: String, branch: String, token: String) -> Self {
-        // NOTE(alvaro): Github apparently is blocking based on user agent (maybe
-        // the problem is a missing user agent?)
-        let curl_ua = "curl/7.68.0";
-        let client = reqwest::blocking::Client::builder()
-            .user_agent(curl_ua)
-            .build()
-            .expect("the client to build");
-
-        Self {
-            username,
-            repo,
-            branch,
-            token,
-            client,
-            last_commit: None,
-        }
-    }
+    /// The forge hosting the remote repository. Guessed from the remote URL
+    /// when not given (self-hosted instances need this to disambiguate)
+    #[arg(long, value_enum)]
+    forge: Option<Forge>,
 
-    pub fn last_commit(&self) -> Option<&str> {
-        self.last_commit.as_deref()
-    }
+    /// Override the forge host, for self-hosted GitLab/Forgejo/Gitea instances
+    #[arg(long)]
+    host: Option<String>,
 
-    fn refresh(&mut self) -> Result<Option<String>> {
-        let url = format!(
-            "https://api.github.com/repos/{}/{}/commits?sha={}&per_page=1",
-            self.username, self.repo, self.branch
-        );
-        trace!("request url={}", &url);
-        let body = self
-            .client
-            .get(url)
-            .bearer_auth(&self.token)
-            .header("Accept", "application/vnd.github+json")
-            .header("X-GitHub-Api-Version", "2022-11-28")
-            .send()?
-            .error_for_status()?;
-
-        let commits: Vec<commit::CommitResponse> = body.json()?;
-        let commit = commits.into_iter().next().ok_or(Error::GitEmptyHistory)?;
-        Ok(self.last_commit.replace(commit.sha))
-    }
+    /// POST a JSON payload describing the outcome of each run to this URL
+    #[arg(long)]
+    notify_webhook: Option<String>,
+
+    /// SMTP host to send email notifications through. Falls back to BRUN_SMTP_HOST
+    #[arg(long)]
+    notify_email_host: Option<String>,
+
+    /// The From address for email notifications. Falls back to BRUN_SMTP_FROM
+    #[arg(long)]
+    notify_email_from: Option<String>,
+
+    /// Recipient addresses for email notifications. Falls back to BRUN_SMTP_TO
+    /// (comma separated)
+    #[arg(long, value_delimiter = ',')]
+    notify_email_to: Vec<String>,
+
+    /// The command to run on upstream changes. NOTE: this is run in a subshell
+    #[arg(last = true, required = true)]
+    cmd: Vec<String>,
 }
 
 fn main() {
@@ -89,10 +96,8 @@ fn main() {
 
     // Parse the arguments
     let args = Args::parse();
-    let user_cmd: String = args.cmd.join(" ");
-    debug!("running with user command: {}", &user_cmd);
 
-    match listen_and_run(user_cmd, args.stop_on_failure, args.period) {
+    match listen_and_run(args) {
         Ok(_) => {}
         Err(error) => {
             eprintln!("error: {}", error);
@@ -101,61 +106,122 @@ fn main() {
     }
 }
 
-fn listen_and_run(user_cmd: String, stop_on_failure: bool, period: f64) -> Result<()> {
-    let mut state = setup()?;
+fn listen_and_run(args: Args) -> Result<()> {
+    let user_cmd: String = args.cmd.join(" ");
+    debug!("running with user command: {}", &user_cmd);
+
+    let stop_on_failure = args.stop_on_failure;
+    let recurse_submodules = args.recurse_submodules;
+    let period = args.period;
+    let debounce = args.debounce;
+
+    let notifiers = build_notifiers(
+        args.notify_webhook,
+        args.notify_email_host,
+        args.notify_email_from,
+        args.notify_email_to,
+    );
+
+    let (repo, mut state) = setup(args.forge, args.host)?;
 
     println!(
         "Listening for changes from {}/{}/{}",
-        &state.username, &state.repo, &state.branch
+        &repo.username, &repo.repo_name, &repo.branch
     );
 
+    // Establish a baseline so the loop below only reacts to genuine
+    // subsequent changes, not the first observation of the current tip
+    state.refresh()?;
+    debug!("baseline commit: {}", state.last_commit().unwrap_or("null"));
+
+    if args.run_on_start {
+        let commit_info = state.last_commit_info();
+        pull_and_run(
+            state.last_commit().unwrap_or("unknown"),
+            commit_info.as_ref(),
+            &user_cmd,
+            stop_on_failure,
+            recurse_submodules,
+            &notifiers,
+        )?;
+    }
+
+    if let Some(addr) = args.webhook {
+        if debounce > 0.0 {
+            return Err(Error::WebhookDebounceUnsupported);
+        }
+
+        let secret = args
+            .webhook_secret
+            .or_else(|| std::env::var("GH_WEBHOOK_SECRET").ok())
+            .ok_or(Error::MissingWebhookSecret)?;
+
+        return webhook::serve(&addr, &secret, &repo.branch, move |after, commit_info| {
+            if state.last_commit() != Some(after) {
+                println!(
+                    "Remote branch changed: {} -> {}",
+                    state.last_commit().unwrap_or("null"),
+                    after
+                );
+                pull_and_run(
+                    after,
+                    commit_info,
+                    &user_cmd,
+                    stop_on_failure,
+                    recurse_submodules,
+                    &notifiers,
+                )?;
+                state.set_last_commit(after.to_string());
+            }
+            Ok(())
+        });
+    }
+
+    // The commit we last ran `cmd` against (the startup baseline until the
+    // first run), so a burst of commits that settles back on it doesn't
+    // trigger a spurious re-run
+    let mut last_run_sha = state.last_commit().map(str::to_string);
+    // The candidate commit waiting out the debounce window, and when it was
+    // first observed
+    let mut pending: Option<(String, Instant)> = None;
+
     // Refresh the state every N seconds
     loop {
         debug!("refreshing git state");
-        let previous = state.refresh()?;
+        state.refresh()?;
+        let current = state.last_commit().map(str::to_string);
         println!(
             "The last commit is: {}",
-            state.last_commit().unwrap_or("null")
+            current.as_deref().unwrap_or("null")
         );
-        if previous.as_deref() != state.last_commit() {
-            // There was a change in the remote
-            println!(
-                "Remote branch changed: {} -> {}",
-                previous.as_deref().unwrap_or("null"),
-                state.last_commit().unwrap_or("null")
-            );
-
-            debug!("running git pull");
-
-            // Pull the latest changes
-            Command::new("git")
-                .arg("pull")
-                .arg("--ff-only")
-                .output()
-                .map_err(|_| Error::CommandFailure("git pull".to_string()))?
-                .status
-                .code()
-                .map(|_| println!("Pulled the latest changes"))
-                .ok_or(Error::CommandSignaled("git pull".to_string()))?;
-
-            debug!("running user command");
-            // Run here the user command
-            let output = Command::new("sh")
-                .arg("-c")
-                .arg(&user_cmd)
-                .output()
-                .map_err(|_| Error::CommandFailure(user_cmd.clone()))?;
-
-            // Show the output of the user command
-            print!("{}", String::from_utf8_lossy(&output.stdout));
-            io::stdout().flush()?;
-
-            if !output.status.success() && stop_on_failure {
-                return Err(Error::UserCommand(
-                    output.status.code().unwrap_or(-1),
-                    String::from_utf8_lossy(&output.stderr).to_string(),
-                ));
+
+        if current != last_run_sha {
+            if pending.as_ref().map(|(sha, _)| sha) != current.as_ref() {
+                println!(
+                    "Remote branch changed: {} -> {}",
+                    last_run_sha.as_deref().unwrap_or("null"),
+                    current.as_deref().unwrap_or("null")
+                );
+                pending = current.clone().map(|sha| (sha, Instant::now()));
+            }
+
+            if let Some((sha, since)) = &pending {
+                if since.elapsed().as_secs_f64() >= debounce {
+                    let commit_info = state.last_commit_info();
+                    pull_and_run(
+                        sha,
+                        commit_info.as_ref(),
+                        &user_cmd,
+                        stop_on_failure,
+                        recurse_submodules,
+                        &notifiers,
+                    )?;
+                    last_run_sha = Some(sha.clone());
+                    pending = None;
+                }
             }
+        } else {
+            pending = None;
         }
 
         trace!("sleeping for {}", period);
@@ -164,13 +230,175 @@ fn listen_and_run(user_cmd: String, stop_on_failure: bool, period: f64) -> Resul
     }
 }
 
-/// Analyze the executing environment and collect the state
-fn setup() -> Result<GithubState> {
-    // Retrieve the token
-    let token = std::env::var("GH_TOKEN")
-        .or_else(|_| std::env::var("GITHUB_TOKEN"))
-        .map_err(|_| Error::MissingToken)?;
+/// Build the configured `Notifier`s from the webhook URL and email settings,
+/// falling back to their BRUN_SMTP_* environment variables when not given.
+/// SMTP credentials, if needed, always come from SMTP_USERNAME/SMTP_PASSWORD
+fn build_notifiers(
+    webhook_url: Option<String>,
+    email_host: Option<String>,
+    email_from: Option<String>,
+    email_to: Vec<String>,
+) -> Vec<Box<dyn Notifier>> {
+    let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+
+    if let Some(url) = webhook_url {
+        notifiers.push(Box::new(WebhookNotifier::new(url)));
+    }
 
+    let email_host = email_host.or_else(|| std::env::var("BRUN_SMTP_HOST").ok());
+    let email_from = email_from.or_else(|| std::env::var("BRUN_SMTP_FROM").ok());
+    let email_to = if email_to.is_empty() {
+        std::env::var("BRUN_SMTP_TO")
+            .map(|to| to.split(',').map(str::to_string).collect())
+            .unwrap_or_default()
+    } else {
+        email_to
+    };
+
+    if let (Some(host), Some(from)) = (email_host, email_from) {
+        if !email_to.is_empty() {
+            let credentials = match (
+                std::env::var("SMTP_USERNAME"),
+                std::env::var("SMTP_PASSWORD"),
+            ) {
+                (Ok(username), Ok(password)) => Some(Credentials::new(username, password)),
+                _ => None,
+            };
+            notifiers.push(Box::new(EmailNotifier::new(
+                host,
+                from,
+                email_to,
+                credentials,
+            )));
+        }
+    }
+
+    notifiers
+}
+
+/// Pull the latest changes via `git pull --ff-only` and run the user command,
+/// notifying every configured `Notifier` of the outcome, and returning an
+/// error if the command failed and `stop_on_failure` is set
+fn pull_and_run(
+    sha: &str,
+    commit_info: Option<&CommitInfo>,
+    user_cmd: &str,
+    stop_on_failure: bool,
+    recurse_submodules: bool,
+    notifiers: &[Box<dyn Notifier>],
+) -> Result<()> {
+    debug!("running git pull");
+
+    // Pull the latest changes
+    let pull_status = Command::new("git")
+        .arg("pull")
+        .arg("--ff-only")
+        .output()
+        .map_err(|_| Error::CommandFailure("git pull".to_string()))?
+        .status;
+    pull_status
+        .code()
+        .map(|_| println!("Pulled the latest changes"))
+        .ok_or(Error::CommandSignaled("git pull".to_string()))?;
+
+    if recurse_submodules && pull_status.success() {
+        debug!("updating submodules");
+        git::git_submodule_update_recursive()?;
+    }
+
+    debug!("running user command");
+    // Run here the user command, streaming its output as it's produced
+    // instead of buffering it until the command exits
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(user_cmd)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|_| Error::CommandFailure(user_cmd.to_string()))?;
+
+    let stdout = child.stdout.take().expect("stdout to be piped");
+    let stderr = child.stderr.take().expect("stderr to be piped");
+
+    let stdout_thread = std::thread::spawn(move || stream_output(stdout, io::stdout()));
+    let stderr_captured = stream_output(stderr, io::stderr())?;
+
+    stdout_thread
+        .join()
+        .expect("stdout streaming thread should not panic")?;
+
+    let status = child.wait()?;
+    let stderr_text = String::from_utf8_lossy(&stderr_captured).to_string();
+
+    let event = RunEvent {
+        sha,
+        commit_info,
+        command: user_cmd,
+        success: status.success(),
+        exit_code: status.code(),
+        stderr: &stderr_text,
+    };
+    for notifier in notifiers {
+        if let Err(err) = notifier.notify(&event) {
+            warn!("notifier failed: {}", err);
+        }
+    }
+
+    if !status.success() && stop_on_failure {
+        return Err(Error::UserCommand(status.code().unwrap_or(-1), stderr_text));
+    }
+
+    Ok(())
+}
+
+/// Copy bytes from `reader` to `writer` as they arrive, returning everything
+/// that was read once the stream is exhausted
+fn stream_output<R: Read, W: Write>(mut reader: R, mut writer: W) -> io::Result<Vec<u8>> {
+    let mut captured = Vec::new();
+    let mut buf = [0u8; 64 * 1024];
+
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                writer.write_all(&buf[..n])?;
+                writer.flush()?;
+                captured.extend_from_slice(&buf[..n]);
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(captured)
+}
+
+/// Retrieve the token to use for `forge` from its conventional environment
+/// variable(s)
+fn token_for_forge(forge: Forge) -> Option<String> {
+    match forge {
+        Forge::Github => std::env::var("GH_TOKEN")
+            .or_else(|_| std::env::var("GITHUB_TOKEN"))
+            .ok(),
+        Forge::Gitlab => std::env::var("GITLAB_TOKEN").ok(),
+        Forge::Forgejo => std::env::var("FORGEJO_TOKEN")
+            .or_else(|_| std::env::var("GITEA_TOKEN"))
+            .ok(),
+    }
+}
+
+/// Analyze the executing environment and collect the remote repository info
+/// together with a `Remote` able to detect upstream changes for it.
+///
+/// The forge is taken from `forge_override` if given, otherwise guessed from
+/// the upstream host (overridden by `host_override` for self-hosted
+/// instances). If the forge's token isn't set, this falls back to detecting
+/// changes with local git commands, which works against any host and needs
+/// no API token; an explicitly selected forge with no token is an error.
+fn setup(
+    forge_override: Option<Forge>,
+    host_override: Option<String>,
+) -> Result<(RemoteRepo, Box<dyn Remote>)> {
     // Check if there are in a git repository work tree
     if !git::git_is_work_tree()? {
         return Err(Error::GitNotinWorkTree);
@@ -187,12 +415,41 @@ fn setup() -> Result<GithubState> {
         return Err(Error::GitDirty);
     }
 
-    Ok(GithubState::new(
-        repo.username.clone(),
-        repo.repo_name.clone(),
-        repo.branch.clone(),
-        token.to_string(),
-    ))
+    let host = host_override.unwrap_or_else(|| repo.host.clone());
+    let forge = forge_override.or_else(|| Forge::detect(&host));
+
+    let selected = forge.and_then(|forge| token_for_forge(forge).map(|token| (forge, token)));
+    let remote: Box<dyn Remote> = match selected {
+        Some((Forge::Github, token)) => Box::new(Github::new(
+            repo.username.clone(),
+            repo.repo_name.clone(),
+            repo.branch.clone(),
+            token,
+        )),
+        Some((Forge::Gitlab, token)) => Box::new(Gitlab::new(
+            host,
+            repo.username.clone(),
+            repo.repo_name.clone(),
+            repo.branch.clone(),
+            token,
+        )),
+        Some((Forge::Forgejo, token)) => Box::new(Forgejo::new(
+            host,
+            repo.username.clone(),
+            repo.repo_name.clone(),
+            repo.branch.clone(),
+            token,
+        )),
+        None => {
+            if let Some(forge) = forge_override {
+                return Err(Error::MissingForgeToken(forge.to_string()));
+            }
+            debug!("no token for the detected forge, detecting changes via local git");
+            Box::new(Local::new(repo.remote.clone(), repo.branch.clone()))
+        }
+    };
+
+    Ok((repo, remote))
 }
 
 /// The information about the remote repository
@@ -204,6 +461,10 @@ struct RemoteRepo {
     repo_name: String,
     /// The name of the branch to track
     branch: String,
+    /// The name of the git remote (e.g. `origin`) tracking it
+    remote: String,
+    /// The hostname of the upstream remote (e.g. `github.com`)
+    host: String,
 }
 
 impl RemoteRepo {
@@ -214,13 +475,18 @@ impl RemoteRepo {
         let branch = git::git_head()?;
         debug!("found branch={}", &branch);
         // Extract the information from the upstream remote
-        let (username, repo_name) = git::git_upstream_info(&branch)?;
-        debug!("found username={} repo_name={}", &username, &repo_name);
+        let info = git::git_upstream_info(&branch)?;
+        debug!(
+            "found username={} repo_name={} host={}",
+            &info.username, &info.repo_name, &info.host
+        );
 
         Ok(Self {
-            username,
-            repo_name,
+            username: info.username,
+            repo_name: info.repo_name,
             branch,
+            remote: info.remote,
+            host: info.host,
         })
     }
 }
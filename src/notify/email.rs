@@ -0,0 +1,86 @@
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+
+use crate::error::{Error, Result};
+use crate::notify::{Notifier, RunEvent};
+
+/// Sends an email summarizing the run via SMTP
+#[derive(Debug)]
+pub struct EmailNotifier {
+    host: String,
+    from: String,
+    recipients: Vec<String>,
+    credentials: Option<Credentials>,
+}
+
+impl EmailNotifier {
+    pub fn new(
+        host: String,
+        from: String,
+        recipients: Vec<String>,
+        credentials: Option<Credentials>,
+    ) -> Self {
+        Self {
+            host,
+            from,
+            recipients,
+            credentials,
+        }
+    }
+}
+
+impl Notifier for EmailNotifier {
+    fn notify(&self, event: &RunEvent) -> Result<()> {
+        let status = if event.success { "succeeded" } else { "failed" };
+        let subject = format!(
+            "brun: {} ({})",
+            status,
+            event
+                .commit_info
+                .map(|info| info.subject.as_str())
+                .unwrap_or(event.sha)
+        );
+
+        let mut body = format!(
+            "Command: {}\nCommit: {}\nStatus: {}\n",
+            event.command, event.sha, status
+        );
+        if let Some(info) = event.commit_info {
+            body.push_str(&format!("Author: {}\n", info.author));
+        }
+        if !event.success {
+            body.push_str(&format!("\nstderr:\n{}\n", event.stderr));
+        }
+
+        let mut builder = Message::builder()
+            .from(self.from.parse().map_err(|_| {
+                Error::InternalError(format!("invalid from address: {}", self.from))
+            })?)
+            .subject(subject);
+
+        for recipient in &self.recipients {
+            builder = builder.to(recipient.parse().map_err(|_| {
+                Error::InternalError(format!("invalid recipient address: {}", recipient))
+            })?);
+        }
+
+        let email = builder
+            .body(body)
+            .map_err(|_| Error::InternalError("failed to build notification email".to_string()))?;
+
+        let mut builder = SmtpTransport::relay(&self.host).map_err(|_| {
+            Error::InternalError(format!("failed to connect to SMTP host: {}", self.host))
+        })?;
+
+        if let Some(credentials) = self.credentials.clone() {
+            builder = builder.credentials(credentials);
+        }
+
+        builder
+            .build()
+            .send(&email)
+            .map_err(|_| Error::InternalError("failed to send email notification".to_string()))?;
+
+        Ok(())
+    }
+}
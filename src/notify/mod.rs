@@ -0,0 +1,23 @@
+pub mod email;
+pub mod webhook;
+
+use crate::error::Result;
+use crate::remote::CommitInfo;
+
+/// The outcome of a user command run against a newly pulled commit, passed
+/// to every configured `Notifier`
+#[derive(Debug)]
+pub(crate) struct RunEvent<'a> {
+    pub sha: &'a str,
+    pub commit_info: Option<&'a CommitInfo>,
+    pub command: &'a str,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub stderr: &'a str,
+}
+
+/// Reports the outcome of a run. Errors are logged by the caller and never
+/// stop the run itself.
+pub(crate) trait Notifier {
+    fn notify(&self, event: &RunEvent) -> Result<()>;
+}
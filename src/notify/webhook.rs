@@ -0,0 +1,55 @@
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::notify::{Notifier, RunEvent};
+
+/// POSTs a JSON payload describing the run to an arbitrary HTTP endpoint
+#[derive(Debug)]
+pub struct WebhookNotifier {
+    url: String,
+    client: reqwest::blocking::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Payload<'a> {
+    sha: &'a str,
+    subject: Option<&'a str>,
+    author: Option<&'a str>,
+    command: &'a str,
+    success: bool,
+    exit_code: Option<i32>,
+    stderr: &'a str,
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify(&self, event: &RunEvent) -> Result<()> {
+        let payload = Payload {
+            sha: event.sha,
+            subject: event.commit_info.map(|info| info.subject.as_str()),
+            author: event.commit_info.map(|info| info.author.as_str()),
+            command: event.command,
+            success: event.success,
+            exit_code: event.exit_code,
+            stderr: event.stderr,
+        };
+
+        self.client
+            .post(&self.url)
+            .json(&payload)
+            .send()
+            .map_err(Error::APIError)?
+            .error_for_status()
+            .map_err(Error::APIError)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use log::trace;
+
+use crate::commit;
+use crate::error::{Error, Result};
+use crate::remote::{CommitInfo, Remote};
+
+/// A `Remote` backed by the Gitea/Forgejo REST API
+#[derive(Debug)]
+pub struct Forgejo {
+    host: String,
+    username: String,
+    repo: String,
+    branch: String,
+    token: String,
+    client: reqwest::blocking::Client,
+    last_commit: Option<String>,
+    last_commit_info: Option<CommitInfo>,
+}
+
+impl Forgejo {
+    pub fn new(
+        host: String,
+        username: String,
+        repo: String,
+        branch: String,
+        token: String,
+    ) -> Self {
+        Self {
+            host,
+            username,
+            repo,
+            branch,
+            token,
+            client: reqwest::blocking::Client::new(),
+            last_commit: None,
+            last_commit_info: None,
+        }
+    }
+}
+
+impl Remote for Forgejo {
+    fn last_commit(&self) -> Option<&str> {
+        self.last_commit.as_deref()
+    }
+
+    fn refresh(&mut self) -> Result<Option<String>> {
+        let url = format!(
+            "https://{}/api/v1/repos/{}/{}/commits?sha={}&limit=1",
+            self.host, self.username, self.repo, self.branch
+        );
+        trace!("request url={}", &url);
+        let body = self
+            .client
+            .get(url)
+            .header("Authorization", format!("token {}", &self.token))
+            .send()?
+            .error_for_status()?;
+
+        let commits: Vec<commit::ForgejoCommitResponse> = body.json()?;
+        let commit = commits.into_iter().next().ok_or(Error::GitEmptyHistory)?;
+
+        self.last_commit_info = Some(CommitInfo {
+            subject: commit
+                .commit
+                .message
+                .lines()
+                .next()
+                .unwrap_or("")
+                .to_string(),
+            author: commit.commit.author.name.clone(),
+        });
+
+        Ok(self.last_commit.replace(commit.sha))
+    }
+
+    fn set_last_commit(&mut self, sha: String) {
+        self.last_commit = Some(sha);
+    }
+
+    fn last_commit_info(&self) -> Option<CommitInfo> {
+        self.last_commit_info.clone()
+    }
+}
@@ -2,7 +2,7 @@ use log::trace;
 
 use crate::commit;
 use crate::error::{Error, Result};
-use crate::remote::Remote;
+use crate::remote::{CommitInfo, Remote};
 
 #[derive(Debug)]
 pub struct Github {
@@ -12,10 +12,11 @@ pub struct Github {
     token: String,
     client: reqwest::blocking::Client,
     last_commit: Option<String>,
+    last_commit_info: Option<CommitInfo>,
 }
 
-impl Remote for Github {
-    fn new(username: String, repo: String, branch: String, token: String) -> Self {
+impl Github {
+    pub fn new(username: String, repo: String, branch: String, token: String) -> Self {
         // NOTE(alvaro): Github apparently is blocking based on user agent (maybe
         // the problem is a missing user agent?)
         let curl_ua = "curl/7.68.0";
@@ -31,9 +32,12 @@ impl Remote for Github {
             token,
             client,
             last_commit: None,
+            last_commit_info: None,
         }
     }
+}
 
+impl Remote for Github {
     fn last_commit(&self) -> Option<&str> {
         self.last_commit.as_deref()
     }
@@ -55,6 +59,26 @@ impl Remote for Github {
 
         let commits: Vec<commit::CommitResponse> = body.json()?;
         let commit = commits.into_iter().next().ok_or(Error::GitEmptyHistory)?;
+
+        self.last_commit_info = Some(CommitInfo {
+            subject: commit
+                .commit
+                .message
+                .lines()
+                .next()
+                .unwrap_or("")
+                .to_string(),
+            author: commit.commit.author.name.clone(),
+        });
+
         Ok(self.last_commit.replace(commit.sha))
     }
+
+    fn set_last_commit(&mut self, sha: String) {
+        self.last_commit = Some(sha);
+    }
+
+    fn last_commit_info(&self) -> Option<CommitInfo> {
+        self.last_commit_info.clone()
+    }
 }
@@ -0,0 +1,80 @@
+use log::trace;
+
+use crate::commit;
+use crate::error::{Error, Result};
+use crate::remote::{CommitInfo, Remote};
+
+/// A `Remote` backed by the GitLab REST API
+#[derive(Debug)]
+pub struct Gitlab {
+    host: String,
+    username: String,
+    repo: String,
+    branch: String,
+    token: String,
+    client: reqwest::blocking::Client,
+    last_commit: Option<String>,
+    last_commit_info: Option<CommitInfo>,
+}
+
+impl Gitlab {
+    pub fn new(
+        host: String,
+        username: String,
+        repo: String,
+        branch: String,
+        token: String,
+    ) -> Self {
+        Self {
+            host,
+            username,
+            repo,
+            branch,
+            token,
+            client: reqwest::blocking::Client::new(),
+            last_commit: None,
+            last_commit_info: None,
+        }
+    }
+}
+
+impl Remote for Gitlab {
+    fn last_commit(&self) -> Option<&str> {
+        self.last_commit.as_deref()
+    }
+
+    fn refresh(&mut self) -> Result<Option<String>> {
+        // The GitLab API addresses projects by their namespace/name, with
+        // the `/` percent-encoded
+        let project = format!("{}%2F{}", self.username, self.repo);
+        let url = format!(
+            "https://{}/api/v4/projects/{}/repository/commits?ref_name={}&per_page=1",
+            self.host, project, self.branch
+        );
+        trace!("request url={}", &url);
+        let body = self
+            .client
+            .get(url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()?
+            .error_for_status()?;
+
+        let commits: Vec<commit::GitlabCommitResponse> = body.json()?;
+        let commit = commits.into_iter().next().ok_or(Error::GitEmptyHistory)?;
+
+        self.last_commit_info = Some(CommitInfo {
+            subject: commit.title.clone(),
+            author: commit.author_name.clone(),
+        });
+
+        Ok(self.last_commit.replace(commit.id))
+    }
+
+    fn set_last_commit(&mut self, sha: String) {
+        self.last_commit = Some(sha);
+    }
+
+    fn last_commit_info(&self) -> Option<CommitInfo> {
+        self.last_commit_info.clone()
+    }
+}
@@ -0,0 +1,37 @@
+use crate::error::Result;
+use crate::git;
+use crate::remote::Remote;
+
+/// Detects upstream changes using local git commands (`git ls-remote`)
+/// instead of a forge API, so it works against any host with no API token
+#[derive(Debug)]
+pub struct Local {
+    remote: String,
+    branch: String,
+    last_commit: Option<String>,
+}
+
+impl Local {
+    pub fn new(remote: String, branch: String) -> Self {
+        Self {
+            remote,
+            branch,
+            last_commit: None,
+        }
+    }
+}
+
+impl Remote for Local {
+    fn last_commit(&self) -> Option<&str> {
+        self.last_commit.as_deref()
+    }
+
+    fn refresh(&mut self) -> Result<Option<String>> {
+        let sha = git::git_ls_remote(&self.remote, &self.branch)?;
+        Ok(self.last_commit.replace(sha))
+    }
+
+    fn set_last_commit(&mut self, sha: String) {
+        self.last_commit = Some(sha);
+    }
+}
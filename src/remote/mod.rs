@@ -1,9 +1,63 @@
+pub mod forgejo;
 pub mod github;
+pub mod gitlab;
+pub mod local;
+
+use clap::ValueEnum;
 
 use crate::error::Result;
 
+/// Canonical commit metadata a `Remote` may be able to supply, independent
+/// of the forge's own response shape
+#[derive(Debug, Clone)]
+pub(crate) struct CommitInfo {
+    pub subject: String,
+    pub author: String,
+}
+
 pub(crate) trait Remote {
-    fn new(username: String, repo_name: String, branch: String, token: String) -> Self;
     fn last_commit(&self) -> Option<&str>;
     fn refresh(&mut self) -> Result<Option<String>>;
+    /// Record `sha` as the last observed commit without going through
+    /// `refresh`, e.g. when it was pushed to us instead of polled for
+    fn set_last_commit(&mut self, sha: String);
+
+    /// The subject line and author of the last observed commit, when the
+    /// backend already has that information on hand from `refresh`
+    fn last_commit_info(&self) -> Option<CommitInfo> {
+        None
+    }
+}
+
+/// The forge hosting the remote repository, selectable with `--forge` or
+/// guessed from the upstream remote's hostname
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "lowercase")]
+pub(crate) enum Forge {
+    Github,
+    Gitlab,
+    Forgejo,
+}
+
+impl Forge {
+    /// Guess the forge from a remote's hostname. Returns `None` for
+    /// self-hosted hosts, which need `--forge` to disambiguate
+    pub(crate) fn detect(host: &str) -> Option<Self> {
+        match host {
+            "github.com" => Some(Forge::Github),
+            "gitlab.com" => Some(Forge::Gitlab),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Forge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Forge::Github => "github",
+            Forge::Gitlab => "gitlab",
+            Forge::Forgejo => "forgejo",
+        };
+        write!(f, "{}", name)
+    }
 }
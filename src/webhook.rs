@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+use hmac::{Hmac, Mac};
+use log::{debug, info, warn};
+use serde::Deserialize;
+use sha2::Sha256;
+
+use crate::error::{Error, Result};
+use crate::remote::CommitInfo;
+
+/// The maximum size of a webhook request body we're willing to allocate for.
+/// GitHub push payloads are a handful of KiB per commit; this comfortably
+/// covers a push of many commits while rejecting a bogus `Content-Length`
+/// before it's ever acted on
+const MAX_BODY_SIZE: usize = 10 * 1024 * 1024;
+
+/// The fields we care about from a GitHub `push` event payload
+#[derive(Debug, Deserialize)]
+struct PushEvent {
+    #[serde(rename = "ref")]
+    git_ref: String,
+    after: String,
+    head_commit: Option<HeadCommit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HeadCommit {
+    message: String,
+    author: HeadCommitAuthor,
+}
+
+#[derive(Debug, Deserialize)]
+struct HeadCommitAuthor {
+    name: String,
+}
+
+/// Listen for GitHub `push` webhook deliveries on `addr` and call `on_push`
+/// with the new tip SHA (and commit metadata, when GitHub provided it)
+/// whenever `branch` moves.
+///
+/// Requests with a missing or invalid `X-Hub-Signature-256` are rejected with
+/// a 401 before the body is ever parsed. A malformed or oversized individual
+/// request is logged and skipped, but an error from `on_push` means the run
+/// itself failed, so it is propagated out of `serve` the same way it would
+/// be out of the polling loop, stopping the server.
+pub(crate) fn serve(
+    addr: &str,
+    secret: &str,
+    branch: &str,
+    mut on_push: impl FnMut(&str, Option<&CommitInfo>) -> Result<()>,
+) -> Result<()> {
+    let listener = TcpListener::bind(addr).map_err(Error::IOError)?;
+    info!("webhook server listening on {}", addr);
+
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                warn!("failed to accept webhook connection: {}", err);
+                continue;
+            }
+        };
+
+        match handle_connection(&mut stream, secret, branch, &mut on_push) {
+            Ok(()) => {}
+            Err(ConnectionError::Request(err)) => {
+                warn!("failed to handle webhook request: {}", err);
+            }
+            Err(ConnectionError::Run(err)) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+/// A failure while servicing a single webhook request: either the request
+/// itself was unreadable/malformed (logged, the server keeps listening for
+/// the next one), or `on_push` ran and failed (fatal, propagated out of
+/// `serve`)
+enum ConnectionError {
+    Request(Error),
+    Run(Error),
+}
+
+impl From<Error> for ConnectionError {
+    fn from(err: Error) -> Self {
+        ConnectionError::Request(err)
+    }
+}
+
+fn handle_connection(
+    stream: &mut TcpStream,
+    secret: &str,
+    branch: &str,
+    on_push: &mut impl FnMut(&str, Option<&CommitInfo>) -> Result<()>,
+) -> std::result::Result<(), ConnectionError> {
+    let (headers, body) = read_request(stream)?;
+
+    let event = headers
+        .get("x-github-event")
+        .map(String::as_str)
+        .unwrap_or("unknown");
+    debug!("received webhook event: {}", event);
+
+    if !verify_signature(
+        secret,
+        &body,
+        headers.get("x-hub-signature-256").map(String::as_str),
+    ) {
+        warn!("rejecting webhook request: missing or invalid signature");
+        write_response(stream, 401, "invalid signature")?;
+        return Ok(());
+    }
+
+    if event != "push" {
+        write_response(stream, 200, "ignored")?;
+        return Ok(());
+    }
+
+    let push: PushEvent = match serde_json::from_slice(&body) {
+        Ok(push) => push,
+        Err(err) => {
+            warn!("failed to parse push event: {}", err);
+            write_response(stream, 400, "bad request")?;
+            return Ok(());
+        }
+    };
+
+    if push.git_ref == format!("refs/heads/{}", branch) {
+        let commit_info = push.head_commit.as_ref().map(|commit| CommitInfo {
+            subject: commit.message.lines().next().unwrap_or("").to_string(),
+            author: commit.author.name.clone(),
+        });
+        on_push(&push.after, commit_info.as_ref()).map_err(ConnectionError::Run)?;
+    }
+
+    write_response(stream, 200, "ok")?;
+    Ok(())
+}
+
+/// Verify that `signature` is `sha256=<hex HMAC-SHA256(secret, body)>`, using
+/// a constant-time comparison of the digest
+fn verify_signature(secret: &str, body: &[u8], signature: Option<&str>) -> bool {
+    let Some(signature) = signature else {
+        return false;
+    };
+    let Some(hex_digest) = signature.strip_prefix("sha256=") else {
+        return false;
+    };
+    let Ok(expected) = hex::decode(hex_digest) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Read a raw HTTP/1.1 request from `stream`, returning its lowercased
+/// headers and body. Only `Content-Length` bodies are supported, which is
+/// all GitHub webhook deliveries use. Rejects a `Content-Length` above
+/// `MAX_BODY_SIZE` before allocating, since it comes from the client and is
+/// read before the request is authenticated.
+fn read_request(stream: &mut TcpStream) -> Result<(HashMap<String, String>, Vec<u8>)> {
+    let mut reader = BufReader::new(stream.try_clone().map_err(Error::IOError)?);
+
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .map_err(Error::IOError)?;
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).map_err(Error::IOError)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let content_length: usize = headers
+        .get("content-length")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0);
+
+    if content_length > MAX_BODY_SIZE {
+        return Err(Error::WebhookBodyTooLarge(content_length, MAX_BODY_SIZE));
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).map_err(Error::IOError)?;
+
+    Ok((headers, body))
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, body: &str) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        _ => "Internal Server Error",
+    };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    stream
+        .write_all(response.as_bytes())
+        .map_err(Error::IOError)
+}